@@ -0,0 +1,371 @@
+use crate::git::{self, run_git_command, GitRepo};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A worktree entry as reported by `GitBackend::worktree_list`.
+#[derive(Debug, Clone)]
+pub struct WorktreeEntry {
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    pub head: String,
+}
+
+/// Abstraction over the git operations workty needs, so the CLI shell-out path
+/// (`CliBackend`) and an in-process libgit2 path (`Libgit2Backend`) can be swapped
+/// via `Config.backend` without touching call sites.
+pub trait GitBackend {
+    fn worktree_add(&self, path: &Path, branch: &str, from: &str) -> Result<()>;
+    fn worktree_list(&self) -> Result<Vec<WorktreeEntry>>;
+    fn worktree_remove(&self, path: &Path, force: bool) -> Result<()>;
+    fn branch_exists(&self, branch: &str) -> bool;
+    fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool>;
+    fn default_branch(&self) -> Option<String>;
+    fn origin_url(&self) -> Option<String>;
+    fn dirty_count(&self, path: &Path) -> Result<usize>;
+}
+
+/// Builds the backend selected by `Config.backend` ("cli" by default, "libgit2" opt-in).
+pub fn build_backend(start_path: Option<&Path>, kind: &str) -> Result<Box<dyn GitBackend>> {
+    match kind {
+        "libgit2" => Ok(Box::new(Libgit2Backend::discover(start_path)?)),
+        _ => Ok(Box::new(CliBackend::new(GitRepo::discover(start_path)?))),
+    }
+}
+
+/// Shells out to the `git` binary for every operation, via `GitRepo`.
+pub struct CliBackend {
+    pub repo: GitRepo,
+}
+
+impl CliBackend {
+    pub fn new(repo: GitRepo) -> Self {
+        Self { repo }
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn worktree_add(&self, path: &Path, branch: &str, from: &str) -> Result<()> {
+        let path_str = path.to_str().context("worktree path is not valid UTF-8")?;
+        self.repo
+            .run_git(&["worktree", "add", "-b", branch, path_str, from])?;
+        Ok(())
+    }
+
+    fn worktree_list(&self) -> Result<Vec<WorktreeEntry>> {
+        let output = self.repo.run_git(&["worktree", "list", "--porcelain"])?;
+        Ok(parse_worktree_list(&output))
+    }
+
+    fn worktree_remove(&self, path: &Path, force: bool) -> Result<()> {
+        let path_str = path.to_str().context("worktree path is not valid UTF-8")?;
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(path_str);
+        self.repo.run_git(&args)?;
+        Ok(())
+    }
+
+    fn branch_exists(&self, branch: &str) -> bool {
+        git::branch_exists(&self.repo, branch)
+    }
+
+    fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool> {
+        git::is_ancestor(&self.repo, ancestor, descendant)
+    }
+
+    fn default_branch(&self) -> Option<String> {
+        self.repo.default_branch()
+    }
+
+    fn origin_url(&self) -> Option<String> {
+        self.repo.origin_url()
+    }
+
+    fn dirty_count(&self, path: &Path) -> Result<usize> {
+        let output = run_git_command(Some(path), &["status", "--porcelain"])?;
+        Ok(output.lines().filter(|line| !line.is_empty()).count())
+    }
+}
+
+fn parse_worktree_list(output: &str) -> Vec<WorktreeEntry> {
+    let mut entries = Vec::new();
+    let mut path: Option<PathBuf> = None;
+    let mut head = String::new();
+    let mut branch = None;
+
+    for line in output.lines() {
+        if let Some(p) = line.strip_prefix("worktree ") {
+            if let Some(prev) = path.take() {
+                entries.push(WorktreeEntry {
+                    path: prev,
+                    branch: branch.take(),
+                    head: std::mem::take(&mut head),
+                });
+            }
+            path = Some(PathBuf::from(p));
+        } else if let Some(h) = line.strip_prefix("HEAD ") {
+            head = h.to_string();
+        } else if let Some(b) = line.strip_prefix("branch ") {
+            branch = Some(b.trim_start_matches("refs/heads/").to_string());
+        }
+    }
+    if let Some(prev) = path.take() {
+        entries.push(WorktreeEntry {
+            path: prev,
+            branch,
+            head,
+        });
+    }
+    entries
+}
+
+/// Resolves the same operations in-process via `git2`, so `list`/`go` stay fast on
+/// large repos and workty keeps working in environments without a `git` binary.
+pub struct Libgit2Backend {
+    pub repo: git2::Repository,
+    pub root: PathBuf,
+}
+
+impl Libgit2Backend {
+    pub fn discover(start_path: Option<&Path>) -> Result<Self> {
+        let working_directory = start_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+        let repo = git2::Repository::discover(&working_directory)
+            .context("Failed to discover git repository via libgit2")?;
+        let root = repo
+            .workdir()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| repo.path().to_path_buf());
+
+        Ok(Self { repo, root })
+    }
+}
+
+impl GitBackend for Libgit2Backend {
+    fn worktree_add(&self, path: &Path, branch: &str, from: &str) -> Result<()> {
+        let from_commit = self
+            .repo
+            .revparse_single(from)
+            .with_context(|| format!("Failed to resolve '{}'", from))?
+            .peel_to_commit()?;
+        self.repo.branch(branch, &from_commit, false)?;
+
+        // Branch names commonly contain '/' (e.g. "feature/x"), which isn't a valid
+        // worktree name, so the worktree is keyed off the path's file name instead;
+        // the branch itself only lives on the checked-out reference below.
+        let worktree_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .context("worktree path has no file name")?;
+
+        let reference = self.repo.find_reference(&format!("refs/heads/{}", branch))?;
+        let mut opts = git2::WorktreeAddOptions::new();
+        opts.reference(Some(&reference));
+
+        self.repo
+            .worktree(worktree_name, path, Some(&opts))
+            .context("Failed to create worktree via libgit2")?;
+        Ok(())
+    }
+
+    fn worktree_list(&self) -> Result<Vec<WorktreeEntry>> {
+        let mut entries = Vec::new();
+
+        // `Repository::worktrees()` only returns linked worktrees, so the primary
+        // working tree has to be synthesized separately to match `CliBackend`'s
+        // porcelain output, which includes it. It is opened from the common dir so
+        // that discovering from inside a linked worktree still finds the primary one.
+        let primary = git2::Repository::open(self.repo.commondir()).ok();
+        if let Some((primary, root)) = primary
+            .as_ref()
+            .and_then(|p| p.workdir().map(|root| (p, root)))
+        {
+            let head = primary.head().ok();
+            entries.push(WorktreeEntry {
+                path: root.to_path_buf(),
+                branch: head.as_ref().and_then(|h| h.shorthand()).map(String::from),
+                head: head
+                    .as_ref()
+                    .and_then(|h| h.target())
+                    .map(|oid| oid.to_string())
+                    .unwrap_or_default(),
+            });
+        }
+
+        let names = self
+            .repo
+            .worktrees()
+            .context("Failed to list worktrees via libgit2")?;
+
+        for name in names.iter().flatten() {
+            let worktree = self.repo.find_worktree(name)?;
+            let wt_repo = git2::Repository::open_from_worktree(&worktree)?;
+            let head = wt_repo.head().ok();
+
+            entries.push(WorktreeEntry {
+                path: worktree.path().to_path_buf(),
+                branch: head.as_ref().and_then(|h| h.shorthand()).map(String::from),
+                head: head
+                    .as_ref()
+                    .and_then(|h| h.target())
+                    .map(|oid| oid.to_string())
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(entries)
+    }
+
+    fn worktree_remove(&self, path: &Path, _force: bool) -> Result<()> {
+        let name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .context("worktree path has no file name")?;
+        let worktree = self
+            .repo
+            .find_worktree(name)
+            .context("worktree not found via libgit2")?;
+
+        // libgit2 only prunes worktrees it considers invalid; `valid(true)` is
+        // required to remove one that's still checked out. `working_tree(true)` is
+        // unconditional because `prune` has no dirty-check of its own — the caller
+        // already gates on dirtiness before calling `worktree_remove` at all, so
+        // `force` only controls whether that gate is bypassed, not what gets deleted
+        // here, matching `CliBackend`'s `git worktree remove` on a clean worktree.
+        let mut opts = git2::WorktreePruneOptions::new();
+        opts.valid(true);
+        opts.working_tree(true);
+        worktree
+            .prune(Some(&mut opts))
+            .context("Failed to remove worktree via libgit2")?;
+        Ok(())
+    }
+
+    fn branch_exists(&self, branch: &str) -> bool {
+        self.repo
+            .find_branch(branch, git2::BranchType::Local)
+            .is_ok()
+    }
+
+    fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool> {
+        let ancestor_id = self.repo.revparse_single(ancestor)?.id();
+        let descendant_id = self.repo.revparse_single(descendant)?.id();
+        if ancestor_id == descendant_id {
+            // A commit is its own ancestor, matching `git merge-base --is-ancestor`;
+            // `graph_descendant_of` returns false for equal ids.
+            return Ok(true);
+        }
+        Ok(self.repo.graph_descendant_of(descendant_id, ancestor_id)?)
+    }
+
+    fn default_branch(&self) -> Option<String> {
+        let head = self.repo.find_reference("HEAD").ok()?;
+        head.symbolic_target()?
+            .strip_prefix("refs/heads/")
+            .map(String::from)
+    }
+
+    fn origin_url(&self) -> Option<String> {
+        self.repo
+            .find_remote("origin")
+            .ok()?
+            .url()
+            .map(String::from)
+    }
+
+    fn dirty_count(&self, path: &Path) -> Result<usize> {
+        let opened;
+        let repo = if path == self.root {
+            &self.repo
+        } else {
+            opened = git2::Repository::open(path).context("Failed to open worktree via libgit2")?;
+            &opened
+        };
+
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts.include_untracked(true);
+        let statuses = repo.statuses(Some(&mut status_opts))?;
+        Ok(statuses.iter().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn init_repo(dir: &Path) {
+        git(dir, &["init", "-b", "main"]);
+        git(dir, &["config", "user.email", "test@test.com"]);
+        git(dir, &["config", "user.name", "Test User"]);
+        std::fs::write(dir.join("README.md"), "# Test Repo\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-m", "Initial commit"]);
+    }
+
+    #[test]
+    fn test_libgit2_worktree_add_list_remove_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        let backend = Libgit2Backend::discover(Some(repo_dir)).unwrap();
+
+        let worktrees_before = backend.worktree_list().unwrap();
+        assert_eq!(worktrees_before.len(), 1, "only the primary worktree exists yet");
+        assert_eq!(worktrees_before[0].path, backend.root);
+        assert_eq!(worktrees_before[0].branch.as_deref(), Some("main"));
+
+        let wt_path = temp.path().parent().unwrap().join("feature-x-worktree");
+        backend.worktree_add(&wt_path, "feature/x", "main").unwrap();
+        assert!(wt_path.join("README.md").exists());
+
+        let worktrees_after = backend.worktree_list().unwrap();
+        assert_eq!(worktrees_after.len(), 2, "primary plus the new linked worktree");
+        assert!(worktrees_after
+            .iter()
+            .any(|w| w.path == wt_path.canonicalize().unwrap() && w.branch.as_deref() == Some("feature/x")));
+
+        backend.worktree_remove(&wt_path, false).unwrap();
+        assert!(!wt_path.exists(), "worktree directory should be deleted");
+
+        let worktrees_final = backend.worktree_list().unwrap();
+        assert_eq!(worktrees_final.len(), 1, "linked worktree is gone again");
+    }
+
+    #[test]
+    fn test_libgit2_is_ancestor() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        git(repo_dir, &["branch", "feature"]);
+        git(repo_dir, &["checkout", "feature"]);
+        std::fs::write(repo_dir.join("feature.txt"), "feature\n").unwrap();
+        git(repo_dir, &["add", "."]);
+        git(repo_dir, &["commit", "-m", "feature commit"]);
+        git(repo_dir, &["checkout", "main"]);
+
+        let backend = Libgit2Backend::discover(Some(repo_dir)).unwrap();
+
+        assert!(backend.is_ancestor("main", "feature").unwrap());
+        assert!(!backend.is_ancestor("feature", "main").unwrap());
+        assert!(
+            backend.is_ancestor("main", "main").unwrap(),
+            "a commit is its own ancestor"
+        );
+    }
+}
@@ -15,6 +15,23 @@ pub struct Config {
     pub root: String,
     pub layout: String,
     pub open_cmd: Option<String>,
+    /// Which `GitBackend` implementation to use: `"cli"` shells out to the `git`
+    /// binary, `"libgit2"` resolves operations in-process via the `git2` crate.
+    pub backend: String,
+    /// Whether `new` initializes submodules in the worktree it creates: `"auto"` only
+    /// when `.gitmodules` is present, `"always"`, or `"never"`.
+    pub submodules: String,
+    /// Provisioning to run after `new` creates a worktree, so it is immediately usable.
+    pub hooks: HooksConfig,
+}
+
+/// Gitignored paths to carry over into a new worktree and shell commands to run
+/// once it exists. See `hooks::provision`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub post_create: Vec<String>,
+    pub copy: Vec<String>,
 }
 
 impl Default for Config {
@@ -25,6 +42,9 @@ impl Default for Config {
             root: "~/.workty/{repo}-{id}".to_string(),
             layout: "flat".to_string(),
             open_cmd: None,
+            backend: "cli".to_string(),
+            submodules: "auto".to_string(),
+            hooks: HooksConfig::default(),
         }
     }
 }
@@ -155,6 +175,10 @@ mod tests {
         assert_eq!(config.version, 1);
         assert_eq!(config.base, "main");
         assert_eq!(config.layout, "flat");
+        assert_eq!(config.backend, "cli");
+        assert_eq!(config.submodules, "auto");
+        assert!(config.hooks.post_create.is_empty());
+        assert!(config.hooks.copy.is_empty());
     }
 
     #[test]
@@ -190,6 +214,12 @@ mod tests {
             root: "~/.worktrees/{repo}".to_string(),
             layout: "flat".to_string(),
             open_cmd: Some("code".to_string()),
+            backend: "cli".to_string(),
+            submodules: "auto".to_string(),
+            hooks: HooksConfig {
+                post_create: vec!["npm install".to_string()],
+                copy: vec![".env".to_string()],
+            },
         };
 
         let serialized = toml::to_string_pretty(&config).unwrap();
@@ -197,5 +227,6 @@ mod tests {
 
         assert_eq!(config.base, deserialized.base);
         assert_eq!(config.open_cmd, deserialized.open_cmd);
+        assert_eq!(config.hooks, deserialized.hooks);
     }
 }
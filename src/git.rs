@@ -13,29 +13,52 @@ impl GitRepo {
             .map(PathBuf::from)
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-        let root = git_rev_parse(&working_directory, &["--show-toplevel"])?;
-        let common_dir = git_rev_parse(&working_directory, &["--git-common-dir"])?;
+        match git_rev_parse(&working_directory, &["--show-toplevel"]) {
+            Ok(root) => {
+                let root = PathBuf::from(root.trim());
+                let common_dir = git_rev_parse(&working_directory, &["--git-common-dir"])?;
+                let common_dir = resolve_relative(&root, common_dir.trim());
 
-        let root = PathBuf::from(root.trim());
-        let common_dir_str = common_dir.trim();
+                Ok(Self {
+                    root: root.canonicalize().unwrap_or(root),
+                    common_dir: common_dir.canonicalize().unwrap_or(common_dir),
+                })
+            }
+            Err(_) => {
+                // No working tree to show: either a plain bare repo, or we're in/under
+                // the bare-primary layout from `init_bare` (workspace_root/.bare, no
+                // privileged main worktree). `--git-common-dir` still resolves there.
+                let common_dir = git_rev_parse(&working_directory, &["--git-common-dir"])
+                    .context("Failed to discover git repository")?;
+                let common_dir = resolve_relative(&working_directory, common_dir.trim());
+                let common_dir = common_dir.canonicalize().unwrap_or(common_dir);
 
-        let common_dir = if Path::new(common_dir_str).is_absolute() {
-            PathBuf::from(common_dir_str)
-        } else {
-            root.join(common_dir_str)
-        };
+                let root = if common_dir.file_name() == Some(std::ffi::OsStr::new(".bare")) {
+                    common_dir
+                        .parent()
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| common_dir.clone())
+                } else {
+                    common_dir.clone()
+                };
 
-        Ok(Self {
-            root: root.canonicalize().unwrap_or(root),
-            common_dir: common_dir.canonicalize().unwrap_or(common_dir),
-        })
+                Ok(Self {
+                    root: root.canonicalize().unwrap_or(root),
+                    common_dir,
+                })
+            }
+        }
     }
 
     pub fn run_git(&self, args: &[&str]) -> Result<String> {
-        run_git_command(Some(&self.root), args)
+        let working_directory = if self.is_bare_primary() {
+            &self.common_dir
+        } else {
+            &self.root
+        };
+        run_git_command(Some(working_directory), args)
     }
 
-    #[allow(dead_code)]
     pub fn run_git_in(&self, worktree_path: &Path, args: &[&str]) -> Result<String> {
         run_git_command(Some(worktree_path), args)
     }
@@ -72,6 +95,53 @@ impl GitRepo {
 
         None
     }
+
+    /// Clones `url` as a bare repository under `workspace_root` and configures it to
+    /// track every branch, for the "all work is a worktree" layout: no privileged
+    /// main working tree, just the bare repo plus whatever `new` creates alongside it.
+    pub fn init_bare(url: &str, workspace_root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(workspace_root)
+            .with_context(|| format!("Failed to create {}", workspace_root.display()))?;
+
+        let bare_dir = workspace_root.join(".bare");
+        let bare_dir_str = bare_dir
+            .to_str()
+            .context("workspace path is not valid UTF-8")?;
+
+        run_git_command(None, &["clone", "--bare", url, bare_dir_str])
+            .context("Failed to clone bare repository")?;
+
+        run_git_command(
+            Some(&bare_dir),
+            &[
+                "config",
+                "remote.origin.fetch",
+                "+refs/heads/*:refs/remotes/origin/*",
+            ],
+        )
+        .context("Failed to configure remote.origin.fetch")?;
+
+        // A `.git` gitfile pointing at `.bare` lets `GitRepo::discover` (and plain
+        // `git` invocations) resolve the repo from `workspace_root` itself, not just
+        // from inside `.bare` or an existing worktree.
+        std::fs::write(workspace_root.join(".git"), "gitdir: ./.bare\n")
+            .context("Failed to write .git gitfile")?;
+
+        Ok(Self {
+            root: workspace_root
+                .canonicalize()
+                .unwrap_or_else(|_| workspace_root.to_path_buf()),
+            common_dir: bare_dir.canonicalize().unwrap_or(bare_dir),
+        })
+    }
+
+    /// Whether this repo has no privileged main working tree — the layout produced
+    /// by `init_bare`, where `root` holds only the bare `.bare` dir and whatever
+    /// worktrees `new` has created alongside it. `list` should not special-case a
+    /// "main" worktree entry when this is true.
+    pub fn is_bare_primary(&self) -> bool {
+        self.common_dir.file_name() == Some(std::ffi::OsStr::new(".bare"))
+    }
 }
 
 fn git_rev_parse(working_directory: &Path, args: &[&str]) -> Result<String> {
@@ -80,6 +150,16 @@ fn git_rev_parse(working_directory: &Path, args: &[&str]) -> Result<String> {
     run_git_command(Some(working_directory), &cmd_args)
 }
 
+/// Resolves a path returned by git (e.g. from `--git-common-dir`) against `base`
+/// if it isn't already absolute.
+fn resolve_relative(base: &Path, path: &str) -> PathBuf {
+    if Path::new(path).is_absolute() {
+        PathBuf::from(path)
+    } else {
+        base.join(path)
+    }
+}
+
 pub fn run_git_command(working_directory: Option<&Path>, args: &[&str]) -> Result<String> {
     let mut cmd = Command::new("git");
     if let Some(directory) = working_directory {
@@ -131,3 +211,242 @@ pub fn is_ancestor(repo: &GitRepo, ancestor: &str, descendant: &str) -> Result<b
         .context("Failed to check ancestry")?;
     Ok(result.status.success())
 }
+
+/// Initializes submodules inside a freshly created worktree, per `Config.submodules`.
+///
+/// - `"auto"`: only runs if `worktree_path` contains a `.gitmodules` file.
+/// - `"always"`: always runs (a no-op if there are no submodules).
+/// - `"never"`: skipped entirely.
+///
+/// Safe to call again later for a worktree that already existed when upstream gained
+/// submodules; it simply re-runs `submodule update --init --recursive`.
+pub fn init_submodules(repo: &GitRepo, worktree_path: &Path, mode: &str) -> Result<()> {
+    if mode == "never" {
+        return Ok(());
+    }
+
+    if mode == "auto" && !worktree_path.join(".gitmodules").exists() {
+        return Ok(());
+    }
+
+    repo.run_git_in(
+        worktree_path,
+        &["submodule", "update", "--init", "--recursive"],
+    )
+    .context("Failed to initialize submodules")?;
+
+    Ok(())
+}
+
+/// Computes how many commits `branch` is ahead/behind `base`, as `(ahead, behind)`.
+///
+/// Returns `(None, None)` if `base...branch` can't be resolved (e.g. no upstream).
+pub fn ahead_behind(repo: &GitRepo, base: &str, branch: &str) -> Result<(Option<u32>, Option<u32>)> {
+    let range = format!("{}...{}", base, branch);
+    let output = match repo.run_git(&["rev-list", "--left-right", "--count", &range]) {
+        Ok(output) => output,
+        Err(_) => return Ok((None, None)),
+    };
+
+    let mut counts = output.trim().split('\t');
+    let behind = counts.next().and_then(|s| s.parse().ok());
+    let ahead = counts.next().and_then(|s| s.parse().ok());
+    Ok((ahead, behind))
+}
+
+/// In-progress git operation detected in a worktree, so `list` can surface it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationState {
+    Clean,
+    Rebasing,
+    Merging,
+    CherryPicking,
+    Reverting,
+    Bisecting,
+}
+
+impl OperationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationState::Clean => "clean",
+            OperationState::Rebasing => "rebasing",
+            OperationState::Merging => "merging",
+            OperationState::CherryPicking => "cherry-picking",
+            OperationState::Reverting => "reverting",
+            OperationState::Bisecting => "bisecting",
+        }
+    }
+}
+
+/// Probes `worktree_path`'s git dir for markers left behind by an in-progress
+/// rebase, merge, cherry-pick, revert, or bisect.
+pub fn operation_state(worktree_path: &Path) -> Result<OperationState> {
+    let git_dir = worktree_git_dir(worktree_path)?;
+
+    if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+        return Ok(OperationState::Rebasing);
+    }
+    if git_dir.join("MERGE_HEAD").exists() {
+        return Ok(OperationState::Merging);
+    }
+    if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        return Ok(OperationState::CherryPicking);
+    }
+    if git_dir.join("REVERT_HEAD").exists() {
+        return Ok(OperationState::Reverting);
+    }
+    if git_dir.join("BISECT_LOG").exists() {
+        return Ok(OperationState::Bisecting);
+    }
+
+    Ok(OperationState::Clean)
+}
+
+fn worktree_git_dir(worktree_path: &Path) -> Result<PathBuf> {
+    let output = run_git_command(Some(worktree_path), &["rev-parse", "--git-dir"])?;
+    let git_dir = PathBuf::from(output.trim());
+    if git_dir.is_absolute() {
+        Ok(git_dir)
+    } else {
+        Ok(worktree_path.join(git_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn init_repo(dir: &Path) {
+        git(dir, &["init", "-b", "main"]);
+        git(dir, &["config", "user.email", "test@test.com"]);
+        git(dir, &["config", "user.name", "Test User"]);
+        std::fs::write(dir.join("README.md"), "# Test Repo\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-m", "Initial commit"]);
+    }
+
+    #[test]
+    fn test_ahead_behind_counts_and_ordering() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        git(repo_dir, &["branch", "feature"]);
+
+        // main gains a commit feature doesn't have: feature is 1 behind main.
+        std::fs::write(repo_dir.join("base-only.txt"), "base\n").unwrap();
+        git(repo_dir, &["add", "."]);
+        git(repo_dir, &["commit", "-m", "base-only commit"]);
+
+        // feature gains a commit main doesn't have: feature is 1 ahead of main.
+        git(repo_dir, &["checkout", "feature"]);
+        std::fs::write(repo_dir.join("feature-only.txt"), "feature\n").unwrap();
+        git(repo_dir, &["add", "."]);
+        git(repo_dir, &["commit", "-m", "feature-only commit"]);
+        git(repo_dir, &["checkout", "main"]);
+
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+        let (ahead, behind) = ahead_behind(&repo, "main", "feature").unwrap();
+
+        assert_eq!(ahead, Some(1), "feature should be 1 commit ahead of main");
+        assert_eq!(behind, Some(1), "feature should be 1 commit behind main");
+    }
+
+    #[test]
+    fn test_ahead_behind_missing_branch_returns_none() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+        let (ahead, behind) = ahead_behind(&repo, "main", "does-not-exist").unwrap();
+
+        assert_eq!(ahead, None);
+        assert_eq!(behind, None);
+    }
+
+    #[test]
+    fn test_operation_state_clean_by_default() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        assert_eq!(operation_state(repo_dir).unwrap(), OperationState::Clean);
+    }
+
+    #[test]
+    fn test_operation_state_detects_merge_in_progress() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+
+        git(repo_dir, &["checkout", "-b", "side"]);
+        std::fs::write(repo_dir.join("README.md"), "# Side\n").unwrap();
+        git(repo_dir, &["commit", "-am", "side change"]);
+        git(repo_dir, &["checkout", "main"]);
+        std::fs::write(repo_dir.join("README.md"), "# Main\n").unwrap();
+        git(repo_dir, &["commit", "-am", "main change"]);
+
+        // Conflicting merge leaves MERGE_HEAD behind without succeeding.
+        let _ = Command::new("git")
+            .current_dir(repo_dir)
+            .args(["merge", "side"])
+            .output();
+
+        assert_eq!(operation_state(repo_dir).unwrap(), OperationState::Merging);
+    }
+
+    #[test]
+    fn test_init_submodules_never_is_noop() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+        std::fs::write(repo_dir.join(".gitmodules"), "[submodule \"x\"]\n").unwrap();
+
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+        init_submodules(&repo, repo_dir, "never").unwrap();
+    }
+
+    #[test]
+    fn test_init_submodules_auto_without_gitmodules_is_noop() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+        assert!(!repo_dir.join(".gitmodules").exists());
+
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+        init_submodules(&repo, repo_dir, "auto").unwrap();
+    }
+
+    #[test]
+    fn test_init_bare_then_discover_roundtrip() {
+        let source = TempDir::new().unwrap();
+        init_repo(source.path());
+
+        let workspace = TempDir::new().unwrap();
+        let workspace_root = workspace.path().join("ws");
+
+        let source_url = source.path().to_str().unwrap();
+        GitRepo::init_bare(source_url, &workspace_root).unwrap();
+
+        let repo = GitRepo::discover(Some(&workspace_root)).unwrap();
+
+        assert!(repo.is_bare_primary());
+        assert_eq!(
+            repo.common_dir,
+            workspace_root.join(".bare").canonicalize().unwrap()
+        );
+        assert_eq!(repo.root, workspace_root.canonicalize().unwrap());
+        assert_eq!(repo.default_branch().as_deref(), Some("main"));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::HooksConfig;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Copies gitignored paths and runs `post_create` commands inside a freshly created
+/// worktree, so it is immediately buildable (mirrors "initialize everything right
+/// after clone"). Each hook command runs with `worktree_path` as its cwd and
+/// `WORKTY_WORKTREE`/`WORKTY_BRANCH`/`WORKTY_REPO_ROOT` exported.
+///
+/// Aborts on the first failing copy or command; callers are expected to roll back
+/// the worktree they just created if this returns an error.
+pub fn provision(
+    repo_root: &Path,
+    worktree_path: &Path,
+    branch: &str,
+    hooks: &HooksConfig,
+) -> Result<()> {
+    for relative in &hooks.copy {
+        copy_path(repo_root, worktree_path, relative)
+            .with_context(|| format!("Failed to copy '{}' into new worktree", relative))?;
+    }
+
+    for command in &hooks.post_create {
+        run_hook(command, worktree_path, branch, repo_root)
+            .with_context(|| format!("post_create hook failed: {}", command))?;
+    }
+
+    Ok(())
+}
+
+fn copy_path(repo_root: &Path, worktree_path: &Path, relative: &str) -> Result<()> {
+    let source = repo_root.join(relative);
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let destination = worktree_path.join(relative);
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    if source.is_dir() {
+        copy_dir_recursive(&source, &destination)
+    } else {
+        std::fs::copy(&source, &destination).map(|_| ())
+    }
+    .with_context(|| format!("Failed to copy {} to {}", source.display(), destination.display()))
+}
+
+fn copy_dir_recursive(source: &Path, destination: &Path) -> Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let dest_path = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &dest_path)?;
+        } else {
+            std::fs::copy(entry.path(), &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_hook(command: &str, worktree_path: &Path, branch: &str, repo_root: &Path) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(worktree_path)
+        .env("WORKTY_WORKTREE", worktree_path)
+        .env("WORKTY_BRANCH", branch)
+        .env("WORKTY_REPO_ROOT", repo_root)
+        .status()
+        .context("Failed to spawn hook command")?;
+
+    if !status.success() {
+        bail!("hook exited with status {}", status);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_provision_copies_paths_and_runs_hooks() {
+        let repo_root = TempDir::new().unwrap();
+        let worktree = TempDir::new().unwrap();
+
+        std::fs::write(repo_root.path().join(".env"), "SECRET=1\n").unwrap();
+
+        let hooks = HooksConfig {
+            copy: vec![".env".to_string()],
+            post_create: vec!["echo -n \"$WORKTY_BRANCH\" > branch.txt".to_string()],
+        };
+
+        provision(repo_root.path(), worktree.path(), "feature/x", &hooks).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(worktree.path().join(".env")).unwrap(),
+            "SECRET=1\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(worktree.path().join("branch.txt")).unwrap(),
+            "feature/x"
+        );
+    }
+
+    #[test]
+    fn test_provision_missing_copy_source_is_skipped() {
+        let repo_root = TempDir::new().unwrap();
+        let worktree = TempDir::new().unwrap();
+
+        let hooks = HooksConfig {
+            copy: vec!["does-not-exist".to_string()],
+            post_create: vec![],
+        };
+
+        provision(repo_root.path(), worktree.path(), "main", &hooks).unwrap();
+        assert!(!worktree.path().join("does-not-exist").exists());
+    }
+
+    #[test]
+    fn test_provision_aborts_on_failing_hook() {
+        let repo_root = TempDir::new().unwrap();
+        let worktree = TempDir::new().unwrap();
+
+        let hooks = HooksConfig {
+            copy: vec![],
+            post_create: vec!["exit 1".to_string()],
+        };
+
+        assert!(provision(repo_root.path(), worktree.path(), "main", &hooks).is_err());
+    }
+}
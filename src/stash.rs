@@ -0,0 +1,127 @@
+use crate::git::GitRepo;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+/// The stash message workty tags its stashes with, keyed to the branch slug so
+/// `find_stash`/`unstash_worktree` can locate them again.
+fn stash_message(slug: &str) -> String {
+    format!("workty/{}", slug)
+}
+
+/// Stashes all uncommitted and untracked changes in `worktree_path` instead of
+/// discarding them, so `rm --stash` can remove the worktree without losing work.
+///
+/// Verifies the tree is clean afterward; callers should only remove the worktree
+/// once this returns `Ok`.
+pub fn stash_worktree(repo: &GitRepo, worktree_path: &Path, slug: &str) -> Result<()> {
+    let message = stash_message(slug);
+    repo.run_git_in(
+        worktree_path,
+        &["stash", "push", "--include-untracked", "-m", &message],
+    )
+    .context("Failed to stash worktree changes")?;
+
+    let status = repo
+        .run_git_in(worktree_path, &["status", "--porcelain"])
+        .context("Failed to verify worktree is clean after stash")?;
+
+    if !status.trim().is_empty() {
+        bail!("worktree still has uncommitted changes after stash push");
+    }
+
+    Ok(())
+}
+
+/// Finds the most recent stash tagged for `slug`, returning its `stash@{n}` ref.
+pub fn find_stash(repo: &GitRepo, slug: &str) -> Result<Option<String>> {
+    let message = stash_message(slug);
+    let output = repo.run_git(&["stash", "list"]).context("Failed to list stashes")?;
+
+    for line in output.lines() {
+        if let Some((reference, rest)) = line.split_once(": ") {
+            if rest.ends_with(&message) {
+                return Ok(Some(reference.to_string()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Re-applies the stash captured by `stash_worktree` into a freshly created worktree
+/// for the same branch, then drops it so a second `unstash` doesn't double-apply.
+pub fn unstash_worktree(repo: &GitRepo, worktree_path: &Path, slug: &str) -> Result<()> {
+    let stash_ref = find_stash(repo, slug)?
+        .with_context(|| format!("no stash found for '{}'", slug))?;
+
+    repo.run_git_in(worktree_path, &["stash", "apply", &stash_ref])
+        .context("Failed to apply stash to worktree")?;
+
+    repo.run_git(&["stash", "drop", &stash_ref])
+        .context("Failed to drop stash after applying")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn init_repo(dir: &Path) {
+        git(dir, &["init", "-b", "main"]);
+        git(dir, &["config", "user.email", "test@test.com"]);
+        git(dir, &["config", "user.name", "Test User"]);
+        std::fs::write(dir.join("README.md"), "# Test Repo\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-m", "Initial commit"]);
+    }
+
+    #[test]
+    fn test_stash_then_unstash_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+
+        std::fs::write(repo_dir.join("scratch.txt"), "uncommitted work").unwrap();
+
+        stash_worktree(&repo, repo_dir, "test-slug").unwrap();
+
+        let status = repo.run_git(&["status", "--porcelain"]).unwrap();
+        assert!(status.trim().is_empty(), "worktree should be clean after stash");
+        assert!(!repo_dir.join("scratch.txt").exists());
+        assert!(find_stash(&repo, "test-slug").unwrap().is_some());
+
+        unstash_worktree(&repo, repo_dir, "test-slug").unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(repo_dir.join("scratch.txt")).unwrap(),
+            "uncommitted work"
+        );
+        assert!(
+            find_stash(&repo, "test-slug").unwrap().is_none(),
+            "stash should be dropped after unstash"
+        );
+    }
+
+    #[test]
+    fn test_unstash_without_stash_errors() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path();
+        init_repo(repo_dir);
+        let repo = GitRepo::discover(Some(repo_dir)).unwrap();
+
+        assert!(unstash_worktree(&repo, repo_dir, "never-stashed").is_err());
+    }
+}